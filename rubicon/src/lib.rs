@@ -1,3 +1,9 @@
+// `cfg(target_thread_local)` is nightly-only (see rust-lang/rust#29594); it gates
+// `thread_local_native!` below. The `#[thread_local]` attribute itself is also nightly-only, but
+// since it's only ever written into the *caller's* crate (through macro expansion), it's the
+// caller's responsibility to add `#![feature(thread_local)]`, not rubicon's.
+#![cfg_attr(feature = "thread-local-native", feature(cfg_target_thread_local))]
+
 #[cfg(all(feature = "export-globals", feature = "import-globals"))]
 compile_error!("The features `export-globals` and `import-globals` cannot be used together");
 
@@ -39,6 +45,176 @@ impl<T> Deref for TrustedExternDouble<T> {
     }
 }
 
+/// Wrapper around an imported thread-local accessor function, used instead of
+/// [`TrustedExternDouble`] when the `tls-bridge` feature is enabled.
+///
+/// Rather than relying on the exported symbol being a bare `&'static LocalKey<T>` (whose layout
+/// is a `std` implementation detail we don't control), this resolves the address of the
+/// thread-local by calling back into the exporting shared object's accessor function, which
+/// itself goes through `LocalKey::with` (or, for [`thread_local_native!`], takes the
+/// `#[thread_local]` static's address directly). This is slower (an extra call per access) but only
+/// depends on a stable ABI: a C function pointer returning a pointer.
+///
+/// The pointer returned by the accessor is only valid for the duration of the closure passed to
+/// [`TlsBridge::with`] — it must not be stored or dereferenced outside of it.
+#[doc(hidden)]
+pub struct TlsBridge<T: 'static> {
+    pub addr_fn: unsafe extern "C" fn() -> *const T,
+}
+
+impl<T: 'static> TlsBridge<T> {
+    /// Calls `f` with a reference to the thread-local value, resolving its address (on the
+    /// calling thread) through the accessor function each time.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let ptr = unsafe { (self.addr_fn)() };
+        f(unsafe { &*ptr })
+    }
+}
+
+//==============================================================================
+// Layout verification
+//==============================================================================
+
+/// Computes a compact fingerprint of `T`'s layout: its size, alignment, and a hash of
+/// `type_name`, the type as written at the declaration site (via `stringify!`).
+///
+/// `type_name` isn't [`std::any::type_name`] — that one isn't usable in a `const fn`, which this
+/// needs to be, since it's called from the `static` initializer of the companion symbol on the
+/// export side.
+///
+/// This is embedded in exported globals (as a `<name>__rubicon_layout` companion symbol) and
+/// compared against the importer's own fingerprint of `T` to catch the case where the exporting
+/// and importing shared objects were built against different versions of the crate that defines
+/// the global — today, a same-name mismatch like that links fine and produces undefined
+/// behavior. The fingerprint isn't a full type-identity check (a hash can collide, and two
+/// distinct types can share size/align/written-name under type aliases), but it catches the
+/// overwhelmingly common case cheaply and without extra dependencies.
+#[doc(hidden)]
+pub const fn layout_fingerprint<T>(type_name: &str) -> u64 {
+    let size = std::mem::size_of::<T>() as u64;
+    let align = std::mem::align_of::<T>() as u64;
+    let name_hash = fnv1a(type_name.as_bytes());
+    size.wrapping_mul(0x9e3779b97f4a7c15) ^ align.rotate_left(21) ^ name_hash
+}
+
+/// FNV-1a, computed in a `const fn` since `Hasher` isn't usable in const contexts.
+const fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Panics with a descriptive message identifying the mismatched symbol, the importer's own
+/// size/align/type-name for it, and both fingerprint values, unless the `lenient-layout-check`
+/// feature is enabled, in which case it prints a [`soprintln!`] warning instead and lets the
+/// import proceed. The exporter's size/align/type-name are not reported: only its fingerprint is
+/// available on the import side.
+#[cfg(not(feature = "lenient-layout-check"))]
+#[doc(hidden)]
+pub fn report_layout_mismatch<T>(symbol: &str, expected: u64, actual: u64) {
+    panic!(
+        "rubicon: layout mismatch for `{symbol}` (`{}`, size = {}, align = {}): expected \
+         fingerprint {expected:#x}, but the exporting shared object reports {actual:#x}. This \
+         usually means the exporting and importing shared objects were built against different \
+         versions of the crate that defines this global.",
+        std::any::type_name::<T>(),
+        std::mem::size_of::<T>(),
+        std::mem::align_of::<T>(),
+    );
+}
+
+// With `soprintln` disabled, `soprintln!` expands to nothing and this becomes a silent no-op —
+// that's the accepted tradeoff of opting into a downgraded, non-panicking check without also
+// opting into the machinery that would let you see it fire.
+#[cfg(feature = "lenient-layout-check")]
+#[doc(hidden)]
+#[allow(unused_variables)]
+pub fn report_layout_mismatch<T>(symbol: &str, expected: u64, actual: u64) {
+    crate::soprintln!(
+        "rubicon: layout mismatch for `{symbol}` (`{}`, size = {}, align = {}): expected \
+         fingerprint {expected:#x}, but the exporting shared object reports {actual:#x}. This \
+         usually means the exporting and importing shared objects were built against different \
+         versions of the crate that defines this global.",
+        std::any::type_name::<T>(),
+        std::mem::size_of::<T>(),
+        std::mem::align_of::<T>(),
+    );
+}
+
+/// Runs `checked`'s [`Once`](std::sync::Once), comparing `expected` against the current value of
+/// `actual` and reporting a mismatch through [`report_layout_mismatch`]. Shared between
+/// [`LayoutChecked`]'s `Deref` and `TlsBridge` impls, and the generated `__verify_layout` accessor
+/// for `static mut` process-locals (which can't go through `LayoutChecked` since they're accessed
+/// directly rather than through a wrapper), so the check itself is written once.
+#[doc(hidden)]
+pub fn verify_layout_once<T>(checked: &std::sync::Once, symbol: &str, expected: u64, actual: &u64) {
+    checked.call_once(|| {
+        let actual = *actual;
+        if actual != expected {
+            report_layout_mismatch::<T>(symbol, expected, actual);
+        }
+    });
+}
+
+/// Wraps an imported global together with its companion layout fingerprint, verifying it once
+/// (lazily, on first access rather than at load time, since the actual fingerprint can only be
+/// read from the exporting shared object's memory at runtime) against the importer's own
+/// [`layout_fingerprint`] of `Checked`.
+///
+/// `Checked` is the type the fingerprint was computed over (e.g. `u32` for a `process_local!`, or
+/// the wrapped value type for a `thread_local!`, as opposed to its surrounding `LocalKey`); `W`
+/// is the wrapper ([`TrustedExtern`], [`TrustedExternDouble`], or [`TlsBridge`]) that actually
+/// grants access to the imported global once the check passes.
+#[doc(hidden)]
+pub struct LayoutChecked<Checked, W> {
+    pub inner: W,
+    pub symbol: &'static str,
+    pub expected: u64,
+    pub actual: &'static u64,
+    pub checked: std::sync::Once,
+    pub _marker: std::marker::PhantomData<fn() -> Checked>,
+}
+
+impl<Checked, W> LayoutChecked<Checked, W> {
+    #[doc(hidden)]
+    pub const fn new(inner: W, symbol: &'static str, expected: u64, actual: &'static u64) -> Self {
+        Self {
+            inner,
+            symbol,
+            expected,
+            actual,
+            checked: std::sync::Once::new(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn verify(&self) {
+        verify_layout_once::<Checked>(&self.checked, self.symbol, self.expected, self.actual);
+    }
+}
+
+impl<Checked, T, W: Deref<Target = T>> Deref for LayoutChecked<Checked, W> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.verify();
+        &self.inner
+    }
+}
+
+impl<T: 'static> LayoutChecked<T, TlsBridge<T>> {
+    /// Verifies the layout fingerprint (once) and calls `f` with a reference to the thread-local
+    /// value, as per [`TlsBridge::with`].
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.verify();
+        self.inner.with(f)
+    }
+}
+
 //==============================================================================
 // Thread-locals
 //==============================================================================
@@ -74,16 +250,19 @@ macro_rules! thread_local {
     () => {};
 
     ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = const { $expr:expr } $(;)?) => {
-        $crate::thread_local! {
-            $(#[$attrs])*
-            $vis static $name: $ty = $expr;
-        }
+        $crate::thread_local_inner!($(#[$attrs])* $vis $name, $ty, const { $expr });
     };
 
     ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $expr:expr $(;)?) => {
         $crate::thread_local_inner!($(#[$attrs])* $vis $name, $ty, $expr);
     };
 
+    // handle multiple declarations
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = const { $init:expr }; $($rest:tt)*) => (
+        $crate::thread_local_inner!($(#[$attr])* $vis $name, $t, const { $init });
+        $crate::thread_local!($($rest)*);
+    );
+
     // handle multiple declarations
     ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => (
         $crate::thread_local_inner!($(#[$attr])* $vis $name, $t, $init);
@@ -91,9 +270,25 @@ macro_rules! thread_local {
     );
 }
 
-#[cfg(feature = "export-globals")]
+#[cfg(all(feature = "export-globals", not(feature = "tls-bridge")))]
 #[macro_export]
 macro_rules! thread_local_inner {
+    // preserves std's `const {}` fast-init path instead of forwarding a plain initializer expr.
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, const { $expr:expr }) => {
+        $crate::paste! {
+            ::std::thread_local! {
+                $(#[$attrs])*
+                $vis static $name: $ty = const { $expr };
+            }
+
+            #[no_mangle]
+            static [<$name __rubicon_export>]: &::std::thread::LocalKey<$ty> = &$name;
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
+        }
+    };
+
     ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
         $crate::paste! {
             // regular thread-local macro, not exported.
@@ -104,25 +299,246 @@ macro_rules! thread_local_inner {
 
             #[no_mangle]
             static [<$name __rubicon_export>]: &::std::thread::LocalKey<$ty> = &$name;
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
         }
     };
 }
 
-#[cfg(feature = "import-globals")]
+#[cfg(all(feature = "import-globals", not(feature = "tls-bridge")))]
 #[macro_export]
 macro_rules! thread_local_inner {
+    // the initializer is never evaluated on the import side, so `const {}` is just unwrapped.
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, const { $expr:expr }) => {
+        $crate::thread_local_inner!($(#[$attrs])* $vis $name, $ty, $expr);
+    };
+
     ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
         $crate::paste! {
             extern "Rust" {
                 #[link_name = stringify!([<$name __rubicon_export>])]
                 #[allow(improper_ctypes)]
                 static [<$name __rubicon_import>]: &'static ::std::thread::LocalKey<$ty>;
+
+                #[link_name = stringify!([<$name __rubicon_layout>])]
+                static [<$name __rubicon_layout_import>]: u64;
             }
 
             // even though this ends up being not a LocalKey, but a type that Derefs to LocalKey,
             // in practice, most codebases work just fine with this, since they call methods
             // that takes `self: &LocalKey`: they don't see the difference.
-            $vis static $name: $crate::TrustedExternDouble<::std::thread::LocalKey<$ty>> = $crate::TrustedExternDouble(unsafe { &[<$name __rubicon_import>] });
+            $vis static $name: $crate::LayoutChecked<$ty, $crate::TrustedExternDouble<::std::thread::LocalKey<$ty>>> = $crate::LayoutChecked::new(
+                $crate::TrustedExternDouble(unsafe { &[<$name __rubicon_import>] }),
+                stringify!($name),
+                $crate::layout_fingerprint::<$ty>(stringify!($ty)),
+                unsafe { &[<$name __rubicon_layout_import>] },
+            );
+        }
+    };
+}
+
+// The `tls-bridge` variants below trade the bare-symbol-as-`&LocalKey` trick above for an
+// `extern "C"` accessor function, at the cost of an extra call per access. This avoids relying on
+// `LocalKey`'s internal representation staying Deref-compatible across compiler versions, and is
+// the only option on backends where thread-locals aren't `LocalKey`-shaped at all (see
+// `thread_local_native!`).
+
+#[cfg(all(feature = "export-globals", feature = "tls-bridge"))]
+#[macro_export]
+macro_rules! thread_local_inner {
+    // preserves std's `const {}` fast-init path instead of forwarding a plain initializer expr.
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, const { $expr:expr }) => {
+        $crate::paste! {
+            ::std::thread_local! {
+                $(#[$attrs])*
+                $vis static $name: $ty = const { $expr };
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<$name __rubicon_tls_addr>]() -> *const $ty {
+                $name.with(|v| v as *const $ty)
+            }
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
+        }
+    };
+
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
+        $crate::paste! {
+            // regular thread-local macro, not exported.
+            ::std::thread_local! {
+                $(#[$attrs])*
+                $vis static $name: $ty = $expr;
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<$name __rubicon_tls_addr>]() -> *const $ty {
+                $name.with(|v| v as *const $ty)
+            }
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
+        }
+    };
+}
+
+#[cfg(all(feature = "import-globals", feature = "tls-bridge"))]
+#[macro_export]
+macro_rules! thread_local_inner {
+    // the initializer is never evaluated on the import side, so `const {}` is just unwrapped.
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, const { $expr:expr }) => {
+        $crate::thread_local_inner!($(#[$attrs])* $vis $name, $ty, $expr);
+    };
+
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
+        $crate::paste! {
+            extern "C" {
+                #[link_name = stringify!([<$name __rubicon_tls_addr>])]
+                fn [<$name __rubicon_tls_addr>]() -> *const $ty;
+            }
+
+            extern "Rust" {
+                #[link_name = stringify!([<$name __rubicon_layout>])]
+                static [<$name __rubicon_layout_import>]: u64;
+            }
+
+            $vis static $name: $crate::LayoutChecked<$ty, $crate::TlsBridge<$ty>> = $crate::LayoutChecked::new(
+                $crate::TlsBridge {
+                    addr_fn: [<$name __rubicon_tls_addr>],
+                },
+                stringify!($name),
+                $crate::layout_fingerprint::<$ty>(stringify!($ty)),
+                unsafe { &[<$name __rubicon_layout_import>] },
+            );
+        }
+    };
+}
+
+//==============================================================================
+// Native thread-locals (`#[thread_local]`, not `LocalKey`)
+//==============================================================================
+
+/// Imports or exports a native `#[thread_local]` static, depending on the enabled cargo features.
+///
+/// Unlike [`thread_local!`], this doesn't go through `std::thread::LocalKey` at all: on targets
+/// where `cfg(target_thread_local)` holds, a `#[thread_local] static FOO: T` is a plain
+/// address-of-TLS access, and those are exactly the declarations nightly/`no_std`-ish setups need
+/// to deduplicate across dylibs, with no `LocalKey` wrapper to piggyback on.
+///
+/// Requires nightly and the `thread-local-native` feature. With `export-globals` enabled, or with
+/// neither feature enabled, this expands to a `#[thread_local]` static, which is itself unstable
+/// and enabled per-crate — so that crate additionally needs `#![feature(thread_local)]`. With
+/// `import-globals`, no such declaration is emitted (the address is resolved through an
+/// `extern "C"` accessor instead), so the importing crate doesn't need it.
+///
+/// Usage:
+///
+///   ```ignore
+///   #![feature(thread_local)]
+///   use rubicon::thread_local_native;
+///
+///   thread_local_native! {
+///       static FOO: u32 = 42;
+///   }
+///   ```
+///
+/// This will import `FOO` if the `import-globals` feature is enabled, and export it if the
+/// `export-globals` feature is enabled. If neither feature is enabled, this will expand to the
+/// `#[thread_local]` static declaration itself.
+///
+/// The exported accessor resolves `FOO`'s address on the calling thread each time (via the same
+/// [`TlsBridge`] accessor-function technique as `thread_local!`'s `tls-bridge` mode); taking the
+/// address inside the defining shared object and taking it through the import are guaranteed to
+/// yield the same pointer on a given thread.
+#[cfg(feature = "thread-local-native")]
+#[cfg(target_thread_local)]
+#[cfg(not(any(feature = "import-globals", feature = "export-globals")))]
+#[macro_export]
+macro_rules! thread_local_native {
+    () => {};
+
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $expr:expr $(;)?) => {
+        #[thread_local]
+        $(#[$attrs])*
+        $vis static $name: $ty = $expr;
+    };
+
+    // handle multiple declarations
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $expr:expr; $($rest:tt)*) => {
+        #[thread_local]
+        $(#[$attrs])*
+        $vis static $name: $ty = $expr;
+        $crate::thread_local_native!($($rest)*);
+    };
+}
+
+#[cfg(feature = "thread-local-native")]
+#[cfg(target_thread_local)]
+#[cfg(any(feature = "export-globals", feature = "import-globals"))]
+#[macro_export]
+macro_rules! thread_local_native {
+    () => {};
+
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $expr:expr $(;)?) => {
+        $crate::thread_local_native_inner!($(#[$attrs])* $vis $name, $ty, $expr);
+    };
+
+    // handle multiple declarations
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $expr:expr; $($rest:tt)*) => {
+        $crate::thread_local_native_inner!($(#[$attrs])* $vis $name, $ty, $expr);
+        $crate::thread_local_native!($($rest)*);
+    };
+}
+
+#[cfg(feature = "thread-local-native")]
+#[cfg(target_thread_local)]
+#[cfg(feature = "export-globals")]
+#[macro_export]
+macro_rules! thread_local_native_inner {
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
+        $crate::paste! {
+            #[thread_local]
+            $(#[$attrs])*
+            $vis static $name: $ty = $expr;
+
+            #[no_mangle]
+            unsafe extern "C" fn [<$name __rubicon_tls_addr>]() -> *const $ty {
+                &$name as *const $ty
+            }
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
+        }
+    };
+}
+
+#[cfg(feature = "thread-local-native")]
+#[cfg(target_thread_local)]
+#[cfg(feature = "import-globals")]
+#[macro_export]
+macro_rules! thread_local_native_inner {
+    ($(#[$attrs:meta])* $vis:vis $name:ident, $ty:ty, $expr:expr) => {
+        $crate::paste! {
+            extern "C" {
+                #[link_name = stringify!([<$name __rubicon_tls_addr>])]
+                fn [<$name __rubicon_tls_addr>]() -> *const $ty;
+            }
+
+            extern "Rust" {
+                #[link_name = stringify!([<$name __rubicon_layout>])]
+                static [<$name __rubicon_layout_import>]: u64;
+            }
+
+            $vis static $name: $crate::LayoutChecked<$ty, $crate::TlsBridge<$ty>> = $crate::LayoutChecked::new(
+                $crate::TlsBridge {
+                    addr_fn: [<$name __rubicon_tls_addr>],
+                },
+                stringify!($name),
+                $crate::layout_fingerprint::<$ty>(stringify!($ty)),
+                unsafe { &[<$name __rubicon_layout_import>] },
+            );
         }
     };
 }
@@ -147,7 +563,9 @@ macro_rules! thread_local_inner {
 /// If neither feature is enabled, this will expand to the static declaration itself.
 ///
 /// This macro supports multiple declarations, along with `static mut` declarations
-/// (which have a slightly different expansion).
+/// (which have a slightly different expansion). On import, a `static mut FOO` also generates a
+/// `FOO__verify_layout()` function, since (unlike the immutable case) there's no wrapper to check
+/// it lazily on first access — call it yourself to catch an exporter/importer ABI mismatch.
 #[cfg(all(not(feature = "import-globals"), not(feature = "export-globals")))]
 #[macro_export]
 macro_rules! process_local {
@@ -195,6 +613,9 @@ macro_rules! process_local_inner {
             #[export_name = stringify!([<$name __rubicon_export>])]
             $(#[$attrs])*
             $vis static $name: $ty = $expr;
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
         }
     };
 }
@@ -207,6 +628,9 @@ macro_rules! process_local_inner_mut {
             #[export_name = stringify!([<$name __rubicon_export>])]
             $(#[$attrs])*
             $vis static mut $name: $ty = $expr;
+
+            #[no_mangle]
+            static [<$name __rubicon_layout>]: u64 = $crate::layout_fingerprint::<$ty>(stringify!($ty));
         }
     };
 }
@@ -220,9 +644,17 @@ macro_rules! process_local_inner {
                 #[link_name = stringify!([<$name __rubicon_export>])]
                 #[allow(improper_ctypes)]
                 static [<$name __rubicon_import>]: $ty;
+
+                #[link_name = stringify!([<$name __rubicon_layout>])]
+                static [<$name __rubicon_layout_import>]: u64;
             }
 
-            $vis static $name: $crate::TrustedExtern<$ty> = $crate::TrustedExtern(unsafe { &[<$name __rubicon_import>] });
+            $vis static $name: $crate::LayoutChecked<$ty, $crate::TrustedExtern<$ty>> = $crate::LayoutChecked::new(
+                $crate::TrustedExtern(unsafe { &[<$name __rubicon_import>] }),
+                stringify!($name),
+                $crate::layout_fingerprint::<$ty>(stringify!($ty)),
+                unsafe { &[<$name __rubicon_layout_import>] },
+            );
         }
     };
 }
@@ -238,6 +670,24 @@ macro_rules! process_local_inner_mut {
                 #[link_name = stringify!([<$name __rubicon_export>])]
                 #[allow(improper_ctypes)]
                 $vis static mut $name: $ty;
+
+                #[link_name = stringify!([<$name __rubicon_layout>])]
+                static [<$name __rubicon_layout_import>]: u64;
+            }
+
+            // `static mut` globals are accessed directly rather than through a wrapper, so they
+            // can't be layout-checked lazily on first deref the way `LayoutChecked` does for
+            // immutable process-locals. Call this (e.g. once at startup, before touching the
+            // static for the first time) to get the same ABI-mismatch protection.
+            #[allow(non_snake_case)]
+            $vis fn [<$name __verify_layout>]() {
+                static CHECKED: ::std::sync::Once = ::std::sync::Once::new();
+                $crate::verify_layout_once::<$ty>(
+                    &CHECKED,
+                    stringify!($name),
+                    $crate::layout_fingerprint::<$ty>(stringify!($ty)),
+                    unsafe { &[<$name __rubicon_layout_import>] },
+                );
             }
         }
     };
@@ -257,6 +707,29 @@ pub fn shared_object_id() -> u64 {
     &SHARED_OBJECT_ID_REF as *const _ as u64
 }
 
+/// The destination [`soprintln!`] writes formatted lines to. Defaults to `eprintln!`.
+static SINK: std::sync::OnceLock<fn(&str)> = std::sync::OnceLock::new();
+
+/// Registers the destination [`soprintln!`] writes to, in place of the default `eprintln!`.
+///
+/// Only the first call takes effect — like [`std::sync::OnceLock`], later calls are silently
+/// ignored. Useful when redirecting several processes' diagnostic output to separate files would
+/// be more legible than interleaving it all on a shared terminal.
+///
+/// Like [`shared_object_id`]'s backing static, this sink is per shared object on purpose: each
+/// dylib linking rubicon gets its own copy and must call `set_sink` itself if it wants its
+/// `soprintln!` output redirected.
+pub fn set_sink(sink: fn(&str)) {
+    let _ = SINK.set(sink);
+}
+
+#[doc(hidden)]
+pub fn sink() -> fn(&str) {
+    SINK.get()
+        .copied()
+        .unwrap_or((|line: &str| eprintln!("{line}")) as fn(&str))
+}
+
 /// Defined to `I` when importing globals, `E` when exporting globals, and `N` otherwise.
 #[cfg(feature = "import-globals")]
 pub static RUBICON_MODE: &str = "I"; // "import"
@@ -272,6 +745,28 @@ pub static RUBICON_MODE: &str = "N"; // "normal"
 #[cfg(all(feature = "import-globals", feature = "export-globals"))]
 compile_error!("The features \"import-globals\" and \"export-globals\" are mutually exclusive");
 
+/// Whether [`Beacon`] should emit ANSI color escapes.
+///
+/// Honors the `NO_COLOR` convention (<https://no-color.org>) and falls back to detecting whether
+/// stderr is a TTY; set `SO_PRINTLN_COLOR=1` or `SO_PRINTLN_COLOR=0` to force the decision either
+/// way regardless of environment or destination — handy since [`set_sink`] can redirect output
+/// away from stderr without this detection knowing.
+fn color_enabled() -> bool {
+    use std::io::IsTerminal;
+    use std::sync::OnceLock;
+
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        if let Ok(v) = std::env::var("SO_PRINTLN_COLOR") {
+            return v == "1";
+        }
+        if std::env::var_os("NO_COLOR").is_some() {
+            return false;
+        }
+        std::io::stderr().is_terminal()
+    })
+}
+
 /// A `u64` whose 24-bit ANSI color is determined by its value.
 ///
 /// Used by the [`soprintln`] macro to visually distinguish shared objects and threads.
@@ -349,16 +844,25 @@ impl<'a> Beacon<'a> {
 
 impl<'a> std::fmt::Display for Beacon<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "\x1b[48;2;{};{};{}m\x1b[38;2;{};{};{}m{}#{:0x}\x1b[0m",
-            self.bg.0, self.bg.1, self.bg.2, self.fg.0, self.fg.1, self.fg.2, self.name, self.val
-        )
+        if color_enabled() {
+            write!(
+                f,
+                "\x1b[48;2;{};{};{}m\x1b[38;2;{};{};{}m{}#{:0x}\x1b[0m",
+                self.bg.0, self.bg.1, self.bg.2, self.fg.0, self.fg.1, self.fg.2, self.name, self.val
+            )
+        } else {
+            write!(f, "{}#{:0x}", self.name, self.val)
+        }
     }
 }
 
 /// Prints a message, prefixed with a cycling millisecond timestamp (wraps at 99999),
 /// a colorized shared object id, a colorized thread name+id, and the given message.
+///
+/// Writes to stderr by default; call [`set_sink`] once at startup to redirect elsewhere. Unless
+/// `SO_PRINTLN_COLOR` forces it, color is emitted only when stderr looks like a TTY and `NO_COLOR`
+/// isn't set (see [`Beacon`]); when a custom sink redirects away from a terminal, set
+/// `SO_PRINTLN_COLOR=0` to match.
 #[macro_export]
 #[cfg(feature = "soprintln")]
 macro_rules! soprintln {
@@ -393,7 +897,7 @@ macro_rules! soprintln {
                 // FIXME: this is probably not necessary, but without it, rustc complains about
                 // capturing variables in format_args?
                 let msg = format!($($arg)*);
-                eprintln!("{timestamp:05} {so_mode_and_id} {thread} {msg}");
+                ($crate::sink())(&format!("{timestamp:05} {so_mode_and_id} {thread} {msg}"));
             }
         }
     };
@@ -0,0 +1,10 @@
+rubicon::process_local! {
+    pub static PL_MATCHED: u32 = 42;
+    pub static PL_MISMATCHED: u32 = 7;
+    pub static mut PLM_MATCHED: u32 = 42;
+    pub static mut PLM_MISMATCHED: u32 = 7;
+}
+
+rubicon::thread_local! {
+    pub static TL_MATCHED: u32 = 42;
+}
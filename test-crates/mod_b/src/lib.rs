@@ -0,0 +1,13 @@
+rubicon::thread_local_native! {
+    pub static NATIVE_COUNTER: u64 = 0;
+}
+
+#[no_mangle]
+pub extern "Rust" fn init() {
+    let imported = NATIVE_COUNTER.with(|v| v as *const u64 as usize);
+    let exported = nativo::addr();
+    assert_eq!(
+        imported, exported,
+        "NATIVE_COUNTER should resolve to the same address on both sides of the import"
+    );
+}
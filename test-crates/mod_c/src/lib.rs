@@ -0,0 +1,24 @@
+rubicon::thread_local! {
+    pub static BRIDGED_COUNTER: u64 = 0;
+}
+
+#[no_mangle]
+pub extern "Rust" fn init() {
+    let imported = BRIDGED_COUNTER.with(|v| v as *const u64 as usize);
+    let exported = ponto::addr();
+    assert_eq!(
+        imported, exported,
+        "BRIDGED_COUNTER should resolve to the same address as the exporter's real TLS slot, \
+         on this thread"
+    );
+
+    // a different thread has its own TLS slot, so the bridge should follow it rather than
+    // freezing on whichever address it first resolved.
+    let other_thread = std::thread::spawn(|| BRIDGED_COUNTER.with(|v| v as *const u64 as usize))
+        .join()
+        .unwrap();
+    assert_ne!(
+        imported, other_thread,
+        "BRIDGED_COUNTER should resolve to a distinct address on another thread"
+    );
+}
@@ -0,0 +1,61 @@
+rubicon::process_local! {
+    pub static PL_MATCHED: u32 = 42;
+}
+
+// Deliberately imported at the wrong type — the exporter declares `u32` for this symbol — to
+// exercise the layout-fingerprint check: size/align/name all differ, so this should be caught
+// instead of silently aliasing the exporter's `u32` as a `u64`.
+rubicon::process_local! {
+    pub static PL_MISMATCHED: u64 = 0;
+}
+
+rubicon::process_local! {
+    pub static mut PLM_MATCHED: u32 = 42;
+}
+
+// Same deliberate mismatch as `PL_MISMATCHED`, but for the `static mut` path, which is checked
+// through the generated `PLM_MISMATCHED__verify_layout()` instead of lazily on first deref.
+rubicon::process_local! {
+    pub static mut PLM_MISMATCHED: u64 = 0;
+}
+
+rubicon::thread_local! {
+    pub static TL_MATCHED: u32 = 42;
+}
+
+#[no_mangle]
+pub extern "Rust" fn init() {
+    // Matching layouts shouldn't panic on first access.
+    assert_eq!(*PL_MATCHED, 42, "PL_MATCHED layouts match; import should succeed silently");
+    TL_MATCHED.with(|v| {
+        assert_eq!(*v, 42, "TL_MATCHED layouts match; import should succeed silently");
+    });
+    PLM_MATCHED__verify_layout();
+
+    let pl_result = std::panic::catch_unwind(|| *PL_MISMATCHED);
+    let plm_result = std::panic::catch_unwind(PLM_MISMATCHED__verify_layout);
+
+    #[cfg(not(feature = "lenient-layout-check"))]
+    {
+        assert!(
+            pl_result.is_err(),
+            "importing PL_MISMATCHED at the wrong type should panic with a layout mismatch"
+        );
+        assert!(
+            plm_result.is_err(),
+            "importing PLM_MISMATCHED at the wrong type should panic with a layout mismatch"
+        );
+    }
+
+    #[cfg(feature = "lenient-layout-check")]
+    {
+        assert!(
+            pl_result.is_ok(),
+            "lenient-layout-check should let the mismatched import through instead of panicking"
+        );
+        assert!(
+            plm_result.is_ok(),
+            "lenient-layout-check should let the mismatched import through instead of panicking"
+        );
+    }
+}
@@ -0,0 +1,9 @@
+#![feature(thread_local)]
+
+rubicon::thread_local_native! {
+    pub static NATIVE_COUNTER: u64 = 0;
+}
+
+pub fn addr() -> usize {
+    &NATIVE_COUNTER as *const u64 as usize
+}
@@ -0,0 +1,7 @@
+rubicon::thread_local! {
+    pub static BRIDGED_COUNTER: u64 = 0;
+}
+
+pub fn addr() -> usize {
+    BRIDGED_COUNTER.with(|v| v as *const u64 as usize)
+}